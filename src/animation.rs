@@ -0,0 +1,110 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use failure::Error;
+
+use crate::UnicornHatHd;
+
+/// A single frame of an animation: a full 16x16 buffer of pixels, plus how
+/// long it should stay on screen before the next frame is drawn.
+pub struct Frame {
+    /// The frame's pixels, in the same row-major order as
+    /// [`UnicornHatHd::set_pixel`](struct.UnicornHatHd.html#method.set_pixel).
+    pub pixels: [rgb::RGB8; 256],
+    /// How long to hold this frame before advancing.
+    pub duration: Duration,
+}
+
+/// Drive a sequence of [`Frame`](struct.Frame.html)s to a
+/// [`UnicornHatHd`](struct.UnicornHatHd.html), sleeping between frames for
+/// the requested duration.
+///
+/// `frames` is consumed lazily, so a closure-driven
+/// [`std::iter::from_fn`](https://doc.rust-lang.org/std/iter/fn.from_fn.html)
+/// that recomputes a frame each tick (e.g. a sprite stepped by a velocity
+/// vector and bounced off the 0..16 walls) works just as well as a
+/// precomputed `Vec<Frame>`. Sleeps are drift-corrected against each
+/// frame's scheduled time rather than the previous frame's actual time, so
+/// the time spent computing and writing a frame doesn't accumulate into
+/// long-term lag.
+///
+/// Playback stops when `frames` is exhausted, or as soon as `should_stop`
+/// returns `true`.
+pub fn play<I>(
+    hat: &mut UnicornHatHd,
+    frames: I,
+    mut should_stop: impl FnMut() -> bool,
+) -> Result<(), Error>
+where
+    I: IntoIterator<Item = Frame>,
+{
+    let start = Instant::now();
+    let mut scheduled = Duration::from_secs(0);
+
+    for frame in frames {
+        if should_stop() {
+            break;
+        }
+
+        for y in 0..16 {
+            for x in 0..16 {
+                hat.set_pixel(x, y, frame.pixels[(y * 16) + x]);
+            }
+        }
+        hat.display()?;
+
+        scheduled += frame.duration;
+        let elapsed = start.elapsed();
+        if scheduled > elapsed {
+            thread::sleep(scheduled - elapsed);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "fake-hardware"))]
+mod tests {
+    use super::*;
+
+    const WHITE: rgb::RGB8 = rgb::RGB8 {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+    const BLACK: rgb::RGB8 = rgb::RGB8 { r: 0, g: 0, b: 0 };
+
+    fn solid_frame(c: rgb::RGB8) -> Frame {
+        Frame {
+            pixels: [c; 256],
+            duration: Duration::from_secs(0),
+        }
+    }
+
+    #[test]
+    fn plays_every_frame_in_order() {
+        let mut hat = UnicornHatHd::new("ignored").unwrap();
+        let frames = vec![solid_frame(BLACK), solid_frame(WHITE)];
+
+        play(&mut hat, frames, || false).unwrap();
+
+        // The last frame drawn should be the one left in the buffer.
+        assert_eq!(hat.get_pixel(0, 0), WHITE);
+    }
+
+    #[test]
+    fn should_stop_ends_playback_before_later_frames_draw() {
+        let mut hat = UnicornHatHd::new("ignored").unwrap();
+        let frames = vec![solid_frame(BLACK), solid_frame(WHITE), solid_frame(WHITE)];
+
+        let mut calls = 0;
+        play(&mut hat, frames, || {
+            calls += 1;
+            // Stop right before the second (white) frame would be drawn.
+            calls > 1
+        })
+        .unwrap();
+
+        assert_eq!(hat.get_pixel(0, 0), BLACK);
+    }
+}
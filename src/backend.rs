@@ -0,0 +1,38 @@
+#[cfg(feature = "hardware")]
+use std::io::prelude::*;
+
+#[cfg(feature = "hardware")]
+use spidev::Spidev;
+
+#[cfg(feature = "rppal-hardware")]
+use rppal::spi::Spi;
+
+use failure::Error;
+
+/// Abstracts over the SPI implementation used to talk to the Unicorn HAT
+/// HD, so callers aren't locked into whichever HAL this crate happens to
+/// use internally.
+///
+/// Implement this for whatever SPI type the rest of your project is
+/// already using, and hand it to
+/// [`UnicornHatHd::from_spi`](struct.UnicornHatHd.html#method.from_spi).
+pub trait SpiBackend {
+    /// Write a raw buffer of bytes out over the SPI bus.
+    fn write(&mut self, data: &[u8]) -> Result<(), Error>;
+}
+
+#[cfg(feature = "hardware")]
+impl SpiBackend for Spidev {
+    fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        Write::write_all(self, data)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rppal-hardware")]
+impl SpiBackend for Spi {
+    fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        Spi::write(self, data)?;
+        Ok(())
+    }
+}
@@ -1,24 +1,64 @@
 #[cfg(feature = "fake-hardware")]
 extern crate ansi_term;
+extern crate embedded_graphics;
 extern crate failure;
+#[cfg(feature = "marquee")]
+extern crate image;
+#[cfg(feature = "rppal-hardware")]
+extern crate rppal;
 extern crate rgb;
 #[cfg(feature = "hardware")]
 extern crate spidev;
 
+#[cfg(feature = "animation")]
+mod animation;
+mod backend;
+#[cfg(feature = "marquee")]
+mod marquee;
+
+#[cfg(feature = "animation")]
+pub use animation::{play, Frame};
+pub use backend::SpiBackend;
+#[cfg(feature = "marquee")]
+pub use marquee::{load_image, Marquee};
+
 #[cfg(feature = "fake-hardware")]
 use ansi_term::Color::RGB;
 #[cfg(feature = "fake-hardware")]
 use ansi_term::ANSIStrings;
+use embedded_graphics::{
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::Rectangle,
+    Pixel,
+};
 use failure::Error;
-#[cfg(feature = "hardware")]
+#[cfg(any(feature = "hardware", feature = "rppal-hardware"))]
 use rgb::ComponentSlice;
-#[cfg(feature = "hardware")]
-use std::io::prelude::*;
+use std::convert::TryFrom;
 #[cfg(feature = "hardware")]
 use spidev::{SPI_MODE_0, Spidev, SpidevOptions};
 
 const BLACK: rgb::RGB8 = rgb::RGB8 { r: 0, g: 0, b: 0 };
 
+/// Default gamma used to build the perceptual brightness curve, matching
+/// the gamma commonly used for WS2812-style LEDs.
+const DEFAULT_GAMMA: f32 = 2.8;
+
+/// Build a `[u8; 256]` lookup table mapping a linear 8-bit value to its
+/// perceptually-corrected output, `out = round(255 * (i / 255) ^ gamma)`.
+///
+/// Gamma correction keeps low values from vanishing and makes mid-tones
+/// look linear to the eye, which raw 8-bit values sent straight to the
+/// LEDs don't.
+fn gamma_table(gamma: f32) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    for (i, slot) in table.iter_mut().enumerate() {
+        *slot = (255.0 * (i as f32 / 255.0).powf(gamma)).round() as u8;
+    }
+    table
+}
+
 /// Possible rotations of the buffer before displaying to the
 /// Unicorn HAT HD.
 pub enum Rotate {
@@ -32,12 +72,16 @@ pub enum Rotate {
     Rot180,
 }
 
-#[cfg(feature = "hardware")]
+#[cfg(any(feature = "hardware", feature = "rppal-hardware"))]
 /// Provide high-level access to the Unicorn HAT HD.
 pub struct UnicornHatHd {
     leds: [rgb::RGB8; 256],
-    spi: Spidev,
+    spi: Box<dyn SpiBackend>,
     rotation: Rotate,
+    gamma_table: [u8; 256],
+    brightness: f32,
+    differential_updates: bool,
+    shadow: Option<Vec<u8>>,
 }
 
 #[cfg(feature = "fake-hardware")]
@@ -45,6 +89,8 @@ pub struct UnicornHatHd {
 pub struct UnicornHatHd {
     leds: [rgb::RGB8; 256],
     rotation: Rotate,
+    gamma_table: [u8; 256],
+    brightness: f32,
 }
 
 impl UnicornHatHd {
@@ -65,11 +111,46 @@ impl UnicornHatHd {
             .mode(SPI_MODE_0)
             .build();
         spidev.configure(&options)?;
-        Ok(UnicornHatHd {
+        Ok(UnicornHatHd::from_spi(spidev))
+    }
+
+    #[cfg(any(feature = "hardware", feature = "rppal-hardware"))]
+    /// Create a new `UnicornHatHd` from an already set up
+    /// [`SpiBackend`](trait.SpiBackend.html).
+    ///
+    /// This is the backend-agnostic counterpart to [`new`](#method.new):
+    /// rather than hard-coding a `spidev` path, it accepts any SPI
+    /// implementation the rest of your project already uses (`spidev`,
+    /// `rppal`, or your own), as long as it implements `SpiBackend`.
+    pub fn from_spi(backend: impl SpiBackend + 'static) -> UnicornHatHd {
+        UnicornHatHd {
             leds: [BLACK; 256],
-            spi: spidev,
+            spi: Box::new(backend),
             rotation: Rotate::RotNone,
-        })
+            gamma_table: gamma_table(DEFAULT_GAMMA),
+            brightness: 1.0,
+            differential_updates: false,
+            shadow: None,
+        }
+    }
+
+    #[cfg(any(feature = "hardware", feature = "rppal-hardware"))]
+    /// Turn dirty-diffing of the display buffer on or off.
+    ///
+    /// When enabled, [`display`](#method.display) compares the serialized
+    /// bytes it's about to send (after rotation, brightness, and gamma have
+    /// all been applied) against the bytes it sent last time, and skips the
+    /// SPI write entirely if nothing changed, which matters for animations
+    /// where most frames only move a few pixels. The Unicorn HAT HD's SPI
+    /// protocol has no way to address a sub-range of the frame, so only
+    /// this all-or-nothing skip is implemented — a frame that *did* change
+    /// is still sent in full rather than as a partial span. Toggling this
+    /// (in either direction) clears the shadow, so the next `display()`
+    /// always sends a full frame rather than risking a stale comparison.
+    /// Disabled by default, matching the previous always-write behavior.
+    pub fn set_differential_updates(&mut self, enabled: bool) {
+        self.differential_updates = enabled;
+        self.shadow = None;
     }
 
     #[cfg(feature = "fake-hardware")]
@@ -80,6 +161,8 @@ impl UnicornHatHd {
         Ok(UnicornHatHd {
             leds: [BLACK; 256],
             rotation: Rotate::RotNone,
+            gamma_table: gamma_table(DEFAULT_GAMMA),
+            brightness: 1.0,
         })
     }
 
@@ -93,12 +176,54 @@ impl UnicornHatHd {
         self.rotation = rot;
     }
 
-    #[cfg(feature = "hardware")]
+    /// Set the global brightness scale applied to every pixel on output.
+    ///
+    /// `brightness` is a multiplier in `0.0..=1.0` applied to each RGB
+    /// component before it's run through the gamma table in
+    /// [`as_array`](#method.as_array), so `0.5` is roughly "half as bright"
+    /// to the eye rather than half the raw value. This only affects what's
+    /// sent to the display; [`get_pixel`](#method.get_pixel) still returns
+    /// the un-scaled value that was set.
+    pub fn set_brightness(&mut self, brightness: f32) {
+        self.brightness = brightness;
+    }
+
+    /// Apply the gamma table and global brightness scale to a single pixel,
+    /// as it will be sent to the Unicorn HAT HD.
+    fn gamma_corrected(&self, led: rgb::RGB8) -> rgb::RGB8 {
+        let scale = |comp: u8| -> u8 {
+            let scaled = (comp as f32 * self.brightness).clamp(0.0, 255.0) as u8;
+            self.gamma_table[scaled as usize]
+        };
+
+        rgb::RGB8 {
+            r: scale(led.r),
+            g: scale(led.g),
+            b: scale(led.b),
+        }
+    }
+
+    #[cfg(any(feature = "hardware", feature = "rppal-hardware"))]
     /// Write the display buffer to the Unicorn HAT HD.
+    ///
+    /// If [`set_differential_updates`](#method.set_differential_updates) has
+    /// turned on dirty-diffing and the serialized frame (after rotation,
+    /// brightness, and gamma) is unchanged since the last call, this is a
+    /// no-op.
     pub fn display(&mut self) -> Result<(), Error> {
-        self.spi.write(&[0x72])?;
         let data = self.as_array();
+
+        if self.differential_updates && self.shadow.as_deref() == Some(data.as_slice()) {
+            return Ok(());
+        }
+
+        self.spi.write(&[0x72])?;
         self.spi.write(&data)?;
+
+        if self.differential_updates {
+            self.shadow = Some(data);
+        }
+
         Ok(())
     }
 
@@ -109,7 +234,7 @@ impl UnicornHatHd {
         for y in 0..16 {
             let mut line = vec![];
             for x in 0..16 {
-                let pixel = self.get_pixel(x, y);
+                let pixel = self.gamma_corrected(self.get_pixel(x, y));
                 line.push(RGB(pixel.r, pixel.g, pixel.b).paint("*"));
             }
             println!("{}", ANSIStrings(&line));
@@ -137,6 +262,37 @@ impl UnicornHatHd {
         self.leds[(y * 16) + x]
     }
 
+    /// Set every pixel in the buffer to the same RGB value.
+    ///
+    /// This is equivalent to calling [`set_pixel`](#method.set_pixel) for
+    /// every coordinate, but fills `self.leds` in one
+    /// [`slice::fill`](https://doc.rust-lang.org/std/primitive.slice.html#method.fill)
+    /// instead of doing the `(y * 16) + x` index math 256 times.
+    pub fn fill(&mut self, c: rgb::RGB8) {
+        self.leds.fill(c);
+    }
+
+    /// Set every pixel within the `w`x`h` rectangle with top-left corner at
+    /// `(x, y)` to the same RGB value.
+    ///
+    /// The rectangle is clipped to the bounds of the display, so it's safe
+    /// to pass a `w`/`h` that would otherwise run off the edge. Each row of
+    /// the rectangle is a contiguous run in `self.leds`, so it's filled with
+    /// [`slice::fill`](https://doc.rust-lang.org/std/primitive.slice.html#method.fill)
+    /// rather than one `set_pixel` call per pixel.
+    pub fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, c: rgb::RGB8) {
+        let x_end = (x + w).min(16);
+        let y_end = (y + h).min(16);
+
+        for row in y..y_end {
+            let row_start = (row * 16) + x;
+            let row_end = (row * 16) + x_end;
+            if row_start < row_end {
+                self.leds[row_start..row_end].fill(c);
+            }
+        }
+    }
+
     /// Clear the internal buffer of pixel states.
     ///
     /// To clear the display itself, you'll still need to call
@@ -145,7 +301,7 @@ impl UnicornHatHd {
         self.leds = [BLACK; 256];
     }
 
-    #[cfg(feature = "hardware")]
+    #[cfg(any(feature = "hardware", feature = "rppal-hardware"))]
     /// Translate the internal buffer into a `Vec<u8>` of RGB values. The LEDs on
     /// the Unicorn HAT HD are addressed in the following order, with each LED
     /// consisting of three `u8`, one each for the R, G, and B values (assuming no
@@ -163,7 +319,7 @@ impl UnicornHatHd {
             // 4 5 6 => 4 5 6 => 1 2 3 4 5 6 7 8 9
             // 7 8 9    7 8 9
             Rotate::RotNone => for led in self.leds.iter() {
-                arr.extend_from_slice(led.as_slice())
+                arr.extend_from_slice(self.gamma_corrected(*led).as_slice())
             },
             // 1 2 3    7 4 1
             // 4 5 6 => 8 5 2 => 7 4 1 8 5 2 9 6 3
@@ -171,7 +327,7 @@ impl UnicornHatHd {
             Rotate::RotCW90 => for x in 0..16 {
                 for y in (0..16).rev() {
                     let led = self.get_pixel(x, y);
-                    arr.extend_from_slice(led.as_slice());
+                    arr.extend_from_slice(self.gamma_corrected(led).as_slice());
                 }
             },
             // 1 2 3    3 6 9
@@ -180,14 +336,14 @@ impl UnicornHatHd {
             Rotate::RotCCW90 => for x in (0..16).rev() {
                 for y in 0..16 {
                     let led = self.get_pixel(x, y);
-                    arr.extend_from_slice(led.as_slice());
+                    arr.extend_from_slice(self.gamma_corrected(led).as_slice());
                 }
             },
             // 1 2 3    9 8 7
             // 4 5 6 => 6 5 4 => 9 8 7 6 5 4 3 2 1
             // 7 8 9    3 2 1
             Rotate::Rot180 => for led in self.leds.iter().rev() {
-                arr.extend_from_slice(led.as_slice());
+                arr.extend_from_slice(self.gamma_corrected(*led).as_slice());
             },
         }
 
@@ -195,11 +351,218 @@ impl UnicornHatHd {
     }
 }
 
+#[cfg(any(feature = "hardware", feature = "fake-hardware"))]
 impl Default for UnicornHatHd {
     /// Create a `UnicornHatHd` using the default path of "`/dev/spidev0.0`".
     ///
     /// This will panic if the default path is not usable.
+    ///
+    /// Not available for `rppal-hardware` builds: `rppal` addresses an SPI
+    /// bus with a [`Bus`](https://docs.rs/rppal/latest/rppal/spi/enum.Bus.html)/
+    /// [`SlaveSelect`](https://docs.rs/rppal/latest/rppal/spi/enum.SlaveSelect.html)
+    /// pair rather than a path, so there's no `&str` default to fall back
+    /// to — construct via [`from_spi`](#method.from_spi) instead.
     fn default() -> UnicornHatHd {
         UnicornHatHd::new("/dev/spidev0.0").unwrap()
     }
 }
+
+impl OriginDimensions for UnicornHatHd {
+    /// The Unicorn HAT HD is a fixed 16x16 grid of LEDs.
+    fn size(&self) -> Size {
+        Size::new(16, 16)
+    }
+}
+
+impl DrawTarget for UnicornHatHd {
+    type Color = Rgb888;
+    type Error = core::convert::Infallible;
+
+    /// Draw each pixel from an `embedded-graphics` primitive, font, or image
+    /// into the display buffer.
+    ///
+    /// Pixels outside of the 16x16 bounds are silently dropped, as is
+    /// conventional for `DrawTarget` implementations. The buffer is indexed
+    /// the same way [`set_pixel`](#method.set_pixel) is, so any rotation set
+    /// with [`set_rotation`](#method.set_rotation) is applied at
+    /// [`display`](#method.display) time as usual.
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = Rectangle::new(Point::zero(), self.size());
+
+        for Pixel(coord, color) in pixels {
+            if !bounds.contains(coord) {
+                continue;
+            }
+
+            let x = usize::try_from(coord.x).unwrap();
+            let y = usize::try_from(coord.y).unwrap();
+            self.set_pixel(x, y, rgb::RGB8::new(color.r(), color.g(), color.b()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "fake-hardware"))]
+mod tests {
+    use super::*;
+
+    const RED: rgb::RGB8 = rgb::RGB8 { r: 255, g: 0, b: 0 };
+
+    #[test]
+    fn fill_rect_clips_to_the_display_bounds() {
+        let mut hat = UnicornHatHd::new("ignored").unwrap();
+
+        // A 10x10 rect anchored near the bottom-right corner runs off the
+        // edge in both directions; it should be clipped, not panic or wrap.
+        hat.fill_rect(10, 10, 10, 10, RED);
+
+        for y in 0..16 {
+            for x in 0..16 {
+                let expected = if x >= 10 && y >= 10 { RED } else { BLACK };
+                assert_eq!(
+                    hat.get_pixel(x, y),
+                    expected,
+                    "pixel ({}, {}) was {:?}, expected {:?}",
+                    x,
+                    y,
+                    hat.get_pixel(x, y),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fill_rect_fully_outside_the_display_is_a_no_op() {
+        let mut hat = UnicornHatHd::new("ignored").unwrap();
+
+        hat.fill_rect(20, 20, 4, 4, RED);
+
+        for y in 0..16 {
+            for x in 0..16 {
+                assert_eq!(hat.get_pixel(x, y), BLACK);
+            }
+        }
+    }
+
+    #[test]
+    fn gamma_table_endpoints_are_unchanged() {
+        let table = gamma_table(DEFAULT_GAMMA);
+
+        assert_eq!(table[0], 0);
+        assert_eq!(table[255], 255);
+    }
+
+    #[test]
+    fn gamma_table_is_monotonically_non_decreasing() {
+        let table = gamma_table(DEFAULT_GAMMA);
+
+        for window in table.windows(2) {
+            assert!(window[0] <= window[1]);
+        }
+    }
+
+    #[test]
+    fn brightness_scales_output_without_touching_the_stored_pixel() {
+        let mut hat = UnicornHatHd::new("ignored").unwrap();
+        hat.set_pixel(0, 0, RED);
+        hat.set_brightness(0.0);
+
+        // get_pixel always returns what was set, regardless of brightness.
+        assert_eq!(hat.get_pixel(0, 0), RED);
+        // But the gamma-corrected output a brightness of 0 would send is black.
+        assert_eq!(hat.gamma_corrected(hat.get_pixel(0, 0)), BLACK);
+    }
+}
+
+#[cfg(all(test, any(feature = "hardware", feature = "rppal-hardware")))]
+mod differential_update_tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct MockBackend {
+        writes: Rc<RefCell<Vec<Vec<u8>>>>,
+    }
+
+    impl SpiBackend for MockBackend {
+        fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+            self.writes.borrow_mut().push(data.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn unchanged_frames_are_skipped_once_enabled() {
+        let backend = MockBackend::default();
+        let writes = backend.writes.clone();
+        let mut hat = UnicornHatHd::from_spi(backend);
+        hat.set_differential_updates(true);
+
+        hat.display().unwrap();
+        assert_eq!(
+            writes.borrow().len(),
+            2,
+            "first display() sends a command byte and a data frame"
+        );
+
+        hat.display().unwrap();
+        assert_eq!(
+            writes.borrow().len(),
+            2,
+            "an unchanged frame shouldn't hit the SPI bus again"
+        );
+
+        hat.set_pixel(0, 0, rgb::RGB8 { r: 255, g: 255, b: 255 });
+        hat.display().unwrap();
+        assert_eq!(writes.borrow().len(), 4, "a changed pixel should be sent");
+    }
+
+    #[test]
+    fn brightness_only_change_is_not_treated_as_unchanged() {
+        // Regression test: the diff used to compare the raw pixel buffer,
+        // so a brightness/rotation change with no pixel edits was wrongly
+        // skipped and the panel kept showing stale output.
+        let backend = MockBackend::default();
+        let writes = backend.writes.clone();
+        let mut hat = UnicornHatHd::from_spi(backend);
+        hat.set_pixel(0, 0, rgb::RGB8 { r: 100, g: 100, b: 100 });
+        hat.set_differential_updates(true);
+
+        hat.display().unwrap();
+        assert_eq!(writes.borrow().len(), 2);
+
+        hat.set_brightness(0.25);
+        hat.display().unwrap();
+        assert_eq!(
+            writes.borrow().len(),
+            4,
+            "brightness-only change must still be sent"
+        );
+    }
+
+    #[test]
+    fn toggling_differential_updates_forces_a_full_frame() {
+        let backend = MockBackend::default();
+        let writes = backend.writes.clone();
+        let mut hat = UnicornHatHd::from_spi(backend);
+        hat.set_differential_updates(true);
+        hat.display().unwrap();
+        assert_eq!(writes.borrow().len(), 2);
+
+        hat.display().unwrap();
+        assert_eq!(writes.borrow().len(), 2);
+
+        // Disabling and re-enabling must not leave a stale shadow behind
+        // that causes the next frame to be wrongly skipped.
+        hat.set_differential_updates(false);
+        hat.set_differential_updates(true);
+        hat.display().unwrap();
+        assert_eq!(writes.borrow().len(), 4);
+    }
+}
@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use failure::Error;
+
+use crate::UnicornHatHd;
+
+const BLACK: rgb::RGB8 = rgb::RGB8 { r: 0, g: 0, b: 0 };
+
+/// A source image or animation frame larger than the Unicorn HAT HD's 16x16
+/// buffer, windowed onto the display and steppable to build a scrolling
+/// marquee.
+///
+/// `Marquee` doesn't write to a [`UnicornHatHd`](struct.UnicornHatHd.html)
+/// on its own; call [`blit`](#method.blit) each frame to copy the current
+/// window into the display buffer, then call
+/// [`display`](struct.UnicornHatHd.html#method.display) as usual.
+pub struct Marquee {
+    source: Vec<rgb::RGB8>,
+    width: usize,
+    height: usize,
+    offset_x: isize,
+    offset_y: isize,
+}
+
+impl Marquee {
+    /// Create a new `Marquee` from a flat, row-major buffer of pixels and
+    /// its `width`/`height`.
+    ///
+    /// `source.len()` must be `width * height`.
+    pub fn new(source: Vec<rgb::RGB8>, width: usize, height: usize) -> Marquee {
+        assert_eq!(source.len(), width * height);
+
+        Marquee {
+            source,
+            width,
+            height,
+            offset_x: 0,
+            offset_y: 0,
+        }
+    }
+
+    /// Move the display window by `(dx, dy)` pixels, as would typically be
+    /// called once per [`display`](struct.UnicornHatHd.html#method.display)
+    /// to animate a scroll.
+    pub fn scroll_step(&mut self, dx: isize, dy: isize) {
+        self.offset_x += dx;
+        self.offset_y += dy;
+    }
+
+    /// Jump the display window directly to `(x, y)`.
+    pub fn set_offset(&mut self, x: isize, y: isize) {
+        self.offset_x = x;
+        self.offset_y = y;
+    }
+
+    /// Copy the current 16x16 window into the
+    /// [`UnicornHatHd`](struct.UnicornHatHd.html)'s buffer.
+    ///
+    /// Window pixels that fall outside of the source image clamp to
+    /// black, so scrolling all the way off one edge fades the
+    /// display to black rather than wrapping or panicking.
+    pub fn blit(&self, hat: &mut UnicornHatHd) {
+        for y in 0..16isize {
+            for x in 0..16isize {
+                let src_x = x + self.offset_x;
+                let src_y = y + self.offset_y;
+                let pixel = if src_x >= 0
+                    && src_y >= 0
+                    && (src_x as usize) < self.width
+                    && (src_y as usize) < self.height
+                {
+                    self.source[(src_y as usize * self.width) + src_x as usize]
+                } else {
+                    BLACK
+                };
+
+                hat.set_pixel(x as usize, y as usize, pixel);
+            }
+        }
+    }
+}
+
+/// Decode an image file (anything [`image`](https://docs.rs/image) supports,
+/// e.g. PNG or BMP) into a row-major `Vec<rgb::RGB8>` plus its width and
+/// height, suitable for handing to [`Marquee::new`](struct.Marquee.html#method.new).
+pub fn load_image<P: AsRef<Path>>(path: P) -> Result<(Vec<rgb::RGB8>, usize, usize), Error> {
+    let img = image::open(path)?.to_rgb8();
+    let (width, height) = img.dimensions();
+
+    let pixels = img
+        .pixels()
+        .map(|p| rgb::RGB8::new(p[0], p[1], p[2]))
+        .collect();
+
+    Ok((pixels, width as usize, height as usize))
+}
+
+#[cfg(all(test, feature = "fake-hardware"))]
+mod tests {
+    use super::*;
+
+    const WHITE: rgb::RGB8 = rgb::RGB8 {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+
+    fn solid_source(width: usize, height: usize, c: rgb::RGB8) -> Vec<rgb::RGB8> {
+        vec![c; width * height]
+    }
+
+    #[test]
+    fn blit_at_zero_offset_fills_the_top_left_corner() {
+        let marquee = Marquee::new(solid_source(4, 4, WHITE), 4, 4);
+        let mut hat = UnicornHatHd::new("ignored").unwrap();
+
+        marquee.blit(&mut hat);
+
+        for y in 0..16 {
+            for x in 0..16 {
+                let expected = if x < 4 && y < 4 { WHITE } else { BLACK };
+                assert_eq!(hat.get_pixel(x, y), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn scrolling_past_the_source_clamps_to_black_instead_of_wrapping() {
+        let mut marquee = Marquee::new(solid_source(4, 4, WHITE), 4, 4);
+        let mut hat = UnicornHatHd::new("ignored").unwrap();
+
+        // Scroll the 4x4 source entirely off the visible window.
+        marquee.scroll_step(20, 20);
+        marquee.blit(&mut hat);
+
+        for y in 0..16 {
+            for x in 0..16 {
+                assert_eq!(hat.get_pixel(x, y), BLACK);
+            }
+        }
+    }
+
+    #[test]
+    fn negative_offset_clamps_the_top_left_to_black() {
+        let mut marquee = Marquee::new(solid_source(4, 4, WHITE), 4, 4);
+        let mut hat = UnicornHatHd::new("ignored").unwrap();
+
+        // Shift the window up/left of the source, so the source lands two
+        // pixels down and right; the display's top-left two rows and
+        // columns fall outside the source and should be black.
+        marquee.set_offset(-2, -2);
+        marquee.blit(&mut hat);
+
+        assert_eq!(hat.get_pixel(1, 1), BLACK);
+        assert_eq!(hat.get_pixel(2, 2), WHITE);
+    }
+}